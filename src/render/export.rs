@@ -0,0 +1,339 @@
+use pdf_writer::Content;
+use usvg::{FillRule, LineCap, LineJoin, Paint, PathSegment};
+
+use crate::render::path::draw_path;
+use crate::render::postscript::PostScriptWriter;
+use crate::util::helper::ColorExt;
+
+/// The vector format a single path's geometry and paint state can be serialized into.
+///
+/// Both variants are driven by the same [`write_path`] logic; only the final emission of
+/// path geometry and paint operators differs, via [`Export`]. `Pdf` returns a bare
+/// content stream (the same fragment `write::path`'s production fast path emits
+/// mid-document), since a standalone PDF *file* needs the rest of the document structure
+/// this crate already builds elsewhere. `PostScript` has no surrounding document to slot
+/// into, so it returns a complete, independently openable single-page `.eps` file.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum FileFormat {
+    Pdf,
+    PostScript,
+}
+
+/// Export a single path's stroke/fill (solid colors only — gradients and patterns need
+/// PDF resources that have no PostScript equivalent here) and geometry as `format`.
+pub fn export_path(path: &usvg::Path, format: FileFormat) -> Vec<u8> {
+    match format {
+        FileFormat::Pdf => {
+            let mut content = Content::new();
+            write_path(path, &mut content);
+            content.finish()
+        }
+        FileFormat::PostScript => {
+            let mut writer = PostScriptWriter::new();
+            write_path(path, &mut writer);
+            eps_document(segments_bounds(path.data.segments()), &writer.finish()).into_bytes()
+        }
+    }
+}
+
+/// Wrap a PostScript operator stream in the DSC header/trailer that makes it a complete,
+/// independently openable `.eps` file: a `%%BoundingBox` (required by the EPS spec so a
+/// placing application knows the artwork's extent without interpreting the program) and
+/// the `%%EOF` trailer.
+fn eps_document(bounds: (f32, f32, f32, f32), body: &str) -> String {
+    let (x0, y0, x1, y1) = bounds;
+    format!(
+        "%!PS-Adobe-3.0 EPSF-3.0\n\
+         %%BoundingBox: {} {} {} {}\n\
+         %%Pages: 1\n\
+         %%EndComments\n\
+         {body}%%EOF\n",
+        x0.floor() as i32,
+        y0.floor() as i32,
+        x1.ceil() as i32,
+        y1.ceil() as i32,
+    )
+}
+
+/// The smallest axis-aligned box containing every on-curve and control point `segments`
+/// visits, in the path's own coordinate space.
+///
+/// Using curve control points rather than the true (tighter) curve extent is a
+/// deliberate, conservative simplification: a cubic Bézier always lies within the convex
+/// hull of its four points, so this never under-reports the bounding box, only
+/// occasionally over-reports it — harmless for an EPS `%%BoundingBox` hint. Returns all
+/// zeroes for an empty path.
+fn segments_bounds(segments: impl Iterator<Item = PathSegment>) -> (f32, f32, f32, f32) {
+    let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+    let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+
+    let mut expand = |x: f64, y: f64| {
+        min_x = min_x.min(x);
+        min_y = min_y.min(y);
+        max_x = max_x.max(x);
+        max_y = max_y.max(y);
+    };
+
+    for segment in segments {
+        match segment {
+            PathSegment::MoveTo { x, y } | PathSegment::LineTo { x, y } => expand(x, y),
+            PathSegment::CurveTo { x1, y1, x2, y2, x, y } => {
+                expand(x1, y1);
+                expand(x2, y2);
+                expand(x, y);
+            }
+            PathSegment::ClosePath => {}
+        }
+    }
+
+    if min_x.is_finite() {
+        (min_x as f32, min_y as f32, max_x as f32, max_y as f32)
+    } else {
+        (0.0, 0.0, 0.0, 0.0)
+    }
+}
+
+/// Write `path`'s stroke/fill (solid colors only — gradients and patterns need PDF
+/// resources, so callers fall back to a separate `Content`/`PdfWriter`-specific branch
+/// for those) and geometry into `exporter`.
+///
+/// This only touches `exporter` itself (no stream setup or teardown), so it is just as
+/// usable mid-stream — as `write::path` does, continuing the current PDF content stream —
+/// as it is for building a fresh one from scratch, as [`export_path`] does for both
+/// formats.
+pub(crate) fn write_path<E: Export>(path: &usvg::Path, exporter: &mut E) {
+    if let Some(stroke) = &path.stroke {
+        exporter.set_line_width(stroke.width.get() as f32);
+        exporter.set_miter_limit(stroke.miterlimit.get() as f32);
+        exporter.set_line_cap(stroke.linecap);
+        exporter.set_line_join(stroke.linejoin);
+
+        if let Some(dasharray) = &stroke.dasharray {
+            exporter.set_dash_pattern(
+                dasharray.iter().map(|&x| x as f32).collect(),
+                stroke.dashoffset,
+            );
+        }
+
+        if let Paint::Color(c) = &stroke.paint {
+            exporter.set_stroke_color(c.as_array());
+        }
+    }
+
+    if let Some(fill) = &path.fill {
+        if let Paint::Color(c) = &fill.paint {
+            exporter.set_fill_color(c.as_array());
+        }
+    }
+
+    draw_path(path.data.segments(), exporter);
+    exporter.finish_path(path.stroke.is_some(), path.fill.as_ref().map(|f| f.rule));
+}
+
+/// The low-level drawing operations the tree walker needs from whatever format it is
+/// writing into.
+///
+/// [`write_path`] builds up a path and its paint state purely in terms of this trait, so
+/// [`crate::render::postscript::PostScriptWriter`] only needed a new `Export`
+/// implementation to become a second backend, not a fork of [`write_path`] itself.
+pub trait Export {
+    fn move_to(&mut self, x: f32, y: f32);
+    fn line_to(&mut self, x: f32, y: f32);
+    fn cubic_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32);
+    fn close_path(&mut self);
+
+    fn set_line_width(&mut self, width: f32);
+    fn set_miter_limit(&mut self, limit: f32);
+    fn set_line_cap(&mut self, cap: LineCap);
+    fn set_line_join(&mut self, join: LineJoin);
+
+    fn set_stroke_color(&mut self, rgb: [f32; 3]);
+    fn set_fill_color(&mut self, rgb: [f32; 3]);
+    fn set_dash_pattern(&mut self, dasharray: Vec<f32>, dashoffset: f32);
+
+    /// Paint the current path according to `stroke`/`fill` (filling with `fill`'s rule
+    /// when it is `Some`), then clear it; with neither, the path is just ended.
+    fn finish_path(&mut self, stroke: bool, fill: Option<FillRule>);
+}
+
+impl Export for Content {
+    fn move_to(&mut self, x: f32, y: f32) {
+        Content::move_to(self, x, y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        Content::line_to(self, x, y);
+    }
+
+    fn cubic_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        Content::cubic_to(self, x1, y1, x2, y2, x, y);
+    }
+
+    fn close_path(&mut self) {
+        Content::close_path(self);
+    }
+
+    fn set_line_width(&mut self, width: f32) {
+        Content::set_line_width(self, width);
+    }
+
+    fn set_miter_limit(&mut self, limit: f32) {
+        Content::set_miter_limit(self, limit);
+    }
+
+    fn set_line_cap(&mut self, cap: LineCap) {
+        use pdf_writer::types::LineCapStyle;
+
+        Content::set_line_cap(
+            self,
+            match cap {
+                LineCap::Butt => LineCapStyle::ButtCap,
+                LineCap::Round => LineCapStyle::RoundCap,
+                LineCap::Square => LineCapStyle::ProjectingSquareCap,
+            },
+        );
+    }
+
+    fn set_line_join(&mut self, join: LineJoin) {
+        use pdf_writer::types::LineJoinStyle;
+
+        Content::set_line_join(
+            self,
+            match join {
+                LineJoin::Miter => LineJoinStyle::MiterJoin,
+                LineJoin::Round => LineJoinStyle::RoundJoin,
+                LineJoin::Bevel => LineJoinStyle::BevelJoin,
+            },
+        );
+    }
+
+    fn set_stroke_color(&mut self, rgb: [f32; 3]) {
+        Content::set_stroke_color(self, rgb);
+    }
+
+    fn set_fill_color(&mut self, rgb: [f32; 3]) {
+        Content::set_fill_color(self, rgb);
+    }
+
+    fn set_dash_pattern(&mut self, dasharray: Vec<f32>, dashoffset: f32) {
+        Content::set_dash_pattern(self, dasharray, dashoffset);
+    }
+
+    fn finish_path(&mut self, stroke: bool, fill: Option<FillRule>) {
+        match (stroke, fill) {
+            (true, Some(FillRule::NonZero)) => {
+                Content::fill_nonzero_and_stroke(self);
+            }
+            (true, Some(FillRule::EvenOdd)) => {
+                Content::fill_even_odd_and_stroke(self);
+            }
+            (false, Some(FillRule::NonZero)) => {
+                Content::fill_nonzero(self);
+            }
+            (false, Some(FillRule::EvenOdd)) => {
+                Content::fill_even_odd(self);
+            }
+            (true, None) => {
+                Content::stroke(self);
+            }
+            (false, None) => {
+                Content::end_path(self);
+            }
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segments_bounds_covers_moveto_lineto_and_curve_control_points() {
+        let segments = vec![
+            PathSegment::MoveTo { x: 0.0, y: 0.0 },
+            PathSegment::LineTo { x: 10.0, y: 0.0 },
+            PathSegment::CurveTo { x1: 10.0, y1: -5.0, x2: 15.0, y2: 20.0, x: 15.0, y: 5.0 },
+        ];
+
+        assert_eq!(segments_bounds(segments.into_iter()), (0.0, -5.0, 15.0, 20.0));
+    }
+
+    #[test]
+    fn segments_bounds_is_all_zero_for_an_empty_path() {
+        assert_eq!(segments_bounds(std::iter::empty()), (0.0, 0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn eps_document_wraps_the_body_in_a_dsc_header_and_trailer() {
+        let doc = eps_document((0.0, 0.0, 10.0, 10.0), "10 10 moveto\n");
+
+        assert!(doc.starts_with("%!PS-Adobe-3.0 EPSF-3.0\n"));
+        assert!(doc.contains("%%BoundingBox: 0 0 10 10\n"));
+        assert!(doc.contains("10 10 moveto\n"));
+        assert!(doc.trim_end().ends_with("%%EOF"));
+    }
+
+    fn operator_bytes(stroke: bool, fill: Option<FillRule>) -> Vec<u8> {
+        let mut content = Content::new();
+        content.finish_path(stroke, fill);
+        content.finish()
+    }
+
+    fn contains_operator(bytes: &[u8], operator: &str) -> bool {
+        String::from_utf8_lossy(bytes).split_whitespace().any(|token| token == operator)
+    }
+
+    #[test]
+    fn finish_path_fills_nonzero_without_a_stroke() {
+        let bytes = operator_bytes(false, Some(FillRule::NonZero));
+        assert!(contains_operator(&bytes, "f"));
+    }
+
+    #[test]
+    fn finish_path_fills_even_odd_without_a_stroke() {
+        let bytes = operator_bytes(false, Some(FillRule::EvenOdd));
+        assert!(contains_operator(&bytes, "f*"));
+    }
+
+    #[test]
+    fn finish_path_fills_nonzero_and_strokes() {
+        let bytes = operator_bytes(true, Some(FillRule::NonZero));
+        assert!(contains_operator(&bytes, "B"));
+    }
+
+    #[test]
+    fn finish_path_fills_even_odd_and_strokes() {
+        let bytes = operator_bytes(true, Some(FillRule::EvenOdd));
+        assert!(contains_operator(&bytes, "B*"));
+    }
+
+    #[test]
+    fn finish_path_strokes_with_no_fill() {
+        let bytes = operator_bytes(true, None);
+        assert!(contains_operator(&bytes, "S"));
+    }
+
+    #[test]
+    fn finish_path_ends_the_path_with_neither_stroke_nor_fill() {
+        let bytes = operator_bytes(false, None);
+        assert!(contains_operator(&bytes, "n"));
+    }
+
+    #[test]
+    fn draw_path_replays_every_segment_kind() {
+        let segments = vec![
+            PathSegment::MoveTo { x: 0.0, y: 0.0 },
+            PathSegment::LineTo { x: 1.0, y: 0.0 },
+            PathSegment::CurveTo { x1: 1.0, y1: 0.0, x2: 1.0, y2: 1.0, x: 0.0, y: 1.0 },
+            PathSegment::ClosePath,
+        ];
+
+        let mut content = Content::new();
+        draw_path(segments.into_iter(), &mut content);
+        let bytes = content.finish();
+
+        for operator in ["m", "l", "c", "h"] {
+            assert!(contains_operator(&bytes, operator), "missing `{operator}` operator");
+        }
+    }
+}
@@ -1,10 +1,12 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use std::rc::Rc;
 
 use pdf_writer::types::{MaskType, ShadingType};
 use pdf_writer::{Content, Filter, Finish, PdfWriter, Ref};
 use usvg::{
-    LinearGradient, NonZeroRect, NormalizedF32, Paint, RadialGradient, StopOffset,
-    Transform, Units,
+    LinearGradient, NonZeroRect, NormalizedF32, Paint, RadialGradient, SpreadMethod,
+    StopOffset, Transform, Units,
 };
 
 use crate::util::context::Context;
@@ -51,6 +53,7 @@ struct GradientProperties {
     stops: Vec<usvg::Stop>,
     transform: Transform,
     units: Units,
+    spread_method: SpreadMethod,
 }
 
 fn create_linear_gradient(
@@ -66,6 +69,7 @@ fn create_linear_gradient(
         stops: gradient.stops.clone(),
         transform: gradient.transform,
         units: gradient.units,
+        spread_method: gradient.spread_method,
     };
     create_shading_pattern(&properties, parent_bbox, writer, ctx, accumulated_transform)
 }
@@ -77,23 +81,44 @@ fn create_radial_gradient(
     ctx: &mut Context,
     accumulated_transform: &Transform,
 ) -> (Rc<String>, Option<Rc<String>>) {
+    let (fx, fy) = clamp_focal_point(
+        gradient.fx,
+        gradient.fy,
+        gradient.fr.get(),
+        gradient.cx,
+        gradient.cy,
+        gradient.r.get(),
+    );
+
     let properties = GradientProperties {
-        coords: vec![
-            gradient.fx,
-            gradient.fy,
-            0.0,
-            gradient.cx,
-            gradient.cy,
-            gradient.r.get(),
-        ],
+        coords: vec![fx, fy, gradient.fr.get(), gradient.cx, gradient.cy, gradient.r.get()],
         shading_type: ShadingType::Radial,
         stops: gradient.stops.clone(),
         transform: gradient.transform,
         units: gradient.units,
+        spread_method: gradient.spread_method,
     };
     create_shading_pattern(&properties, parent_bbox, writer, ctx, accumulated_transform)
 }
 
+/// PDF's type-3 (radial) shading requires the inner circle to lie inside, or at least
+/// touch, the outer one. usvg does not enforce that `(fx, fy)` stays within `r - fr` of
+/// `(cx, cy)`, so pull the focal point in until the inner circle touches the outer
+/// circle's boundary (leaving a hair's-width margin so rounding doesn't push it back
+/// outside) whenever the inner circle would otherwise extend past it.
+fn clamp_focal_point(fx: f32, fy: f32, fr: f32, cx: f32, cy: f32, r: f32) -> (f32, f32) {
+    let (dx, dy) = (fx - cx, fy - cy);
+    let dist = (dx * dx + dy * dy).sqrt();
+    let max_dist = (r - fr).max(0.0);
+
+    if dist > max_dist && dist > 0.0 {
+        let scale = (max_dist / dist) * 0.999;
+        (cx + dx * scale, cy + dy * scale)
+    } else {
+        (fx, fy)
+    }
+}
+
 fn create_shading_pattern(
     properties: &GradientProperties,
     parent_bbox: &NonZeroRect,
@@ -101,14 +126,13 @@ fn create_shading_pattern(
     ctx: &mut Context,
     accumulated_transform: &Transform,
 ) -> (Rc<String>, Option<Rc<String>>) {
-    let pattern_ref = ctx.alloc_ref();
-
-    let soft_mask = if properties.stops.iter().any(|stop| stop.opacity.get() < 1.0) {
-        Some(get_soft_mask(properties, parent_bbox, writer, ctx))
-    } else {
-        None
-    };
-
+    // `coords` are always the two *circles* (or, for axial, the two *points*) in the
+    // gradient's own untransformed space; every transform that would otherwise skew or
+    // scale them unevenly — the `objectBoundingBox` bbox mapping, the gradient's own
+    // `transform`, and the accumulated node transform — is instead folded into `matrix`.
+    // That keeps radial gradients circular in gradient space even under a non-uniform
+    // (sheared or anisotropically scaled) transform, matching how PDF expects a type-3
+    // shading's two circles to be expressed.
     let matrix = accumulated_transform
         .pre_concat(if properties.units == Units::ObjectBoundingBox {
             Transform::from_bbox(*parent_bbox)
@@ -117,30 +141,298 @@ fn create_shading_pattern(
         })
         .pre_concat(properties.transform);
 
-    let shading_function_ref =
-        get_function(&properties.stops, writer, ctx, false);
-    let mut shading_pattern = writer.shading_pattern(pattern_ref);
-    let mut shading = shading_pattern.shading();
-    shading.shading_type(properties.shading_type);
-    shading.color_space().srgb();
+    // The exact same gradient (same stops, same effective matrix) recurs constantly in
+    // machine-generated SVGs (repeated gradient-filled glyphs/markers); reuse the whole
+    // pattern object instead of re-emitting it every time. The cache only ever holds the
+    // `Ref`s of objects already written to the PDF, never resource *names* — a name is
+    // only meaningful within the Resources dictionary of the XObject/content stream
+    // currently being built, so it has to be re-registered via `ctx.deferrer` on every
+    // hit, not just the first time the gradient is emitted.
+    let cache_key = shading_pattern_cache_key(properties, parent_bbox, &matrix);
+    let (pattern_ref, soft_mask_ref) = match ctx.deferrer.cached_shading_pattern(cache_key) {
+        Some(refs) => refs,
+        None => {
+            let pattern_ref = ctx.alloc_ref();
+
+            let soft_mask_ref = if properties.stops.iter().any(|stop| stop.opacity.get() < 1.0) {
+                Some(get_soft_mask(properties, parent_bbox, writer, ctx))
+            } else {
+                None
+            };
+
+            let (coords, stops, extend) = spread_coords_and_stops(properties, parent_bbox);
+
+            let shading_function_ref = get_function(&stops, writer, ctx, false);
+            let mut shading_pattern = writer.shading_pattern(pattern_ref);
+            let mut shading = shading_pattern.shading();
+            shading.shading_type(properties.shading_type);
+            shading.color_space().srgb();
+
+            shading.function(shading_function_ref);
+            shading.coords(coords.iter().copied());
+            shading.extend([extend, extend]);
+            shading.finish();
+
+            shading_pattern.matrix(matrix.to_pdf_transform());
+            shading_pattern.finish();
+
+            ctx.deferrer.cache_shading_pattern(cache_key, (pattern_ref, soft_mask_ref));
+            (pattern_ref, soft_mask_ref)
+        }
+    };
 
-    shading.function(shading_function_ref);
-    shading.coords(properties.coords.iter().copied());
-    shading.extend([true, true]);
-    shading.finish();
+    let pattern_name = ctx.deferrer.add_pattern(pattern_ref);
+    let soft_mask_name = soft_mask_ref.map(|gs_ref| ctx.deferrer.add_graphics_state(gs_ref));
 
-    shading_pattern.matrix(matrix.to_pdf_transform());
-    shading_pattern.finish();
+    (pattern_name, soft_mask_name)
+}
 
-    (ctx.deferrer.add_pattern(pattern_ref), soft_mask)
+fn hash_f32<H: Hasher>(hasher: &mut H, value: f32) {
+    value.to_bits().hash(hasher);
 }
 
+/// A structural fingerprint of everything that determines a shading pattern's PDF
+/// representation: the gradient's own geometry/stops/spread method, the region it needs
+/// to cover (only relevant for non-`ObjectBoundingBox` spreads), and the matrix it will
+/// ultimately be painted with. Two calls with the same key would write byte-identical
+/// pattern objects, so the second one can just reuse the first's `Ref`.
+fn shading_pattern_cache_key(
+    properties: &GradientProperties,
+    parent_bbox: &NonZeroRect,
+    matrix: &Transform,
+) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    format!("{:?}", properties.shading_type).hash(&mut hasher);
+    format!("{:?}", properties.spread_method).hash(&mut hasher);
+
+    for v in &properties.coords {
+        hash_f32(&mut hasher, *v);
+    }
+
+    for stop in &properties.stops {
+        hash_f32(&mut hasher, stop.offset.get());
+        hash_f32(&mut hasher, stop.opacity.get());
+        for c in stop.color_stops().color {
+            hash_f32(&mut hasher, c);
+        }
+    }
+
+    hash_f32(&mut hasher, parent_bbox.x() as f32);
+    hash_f32(&mut hasher, parent_bbox.y() as f32);
+    hash_f32(&mut hasher, parent_bbox.width() as f32);
+    hash_f32(&mut hasher, parent_bbox.height() as f32);
+
+    for v in matrix.to_pdf_transform() {
+        hash_f32(&mut hasher, v);
+    }
+
+    hasher.finish()
+}
+
+/// Resolve `properties.spread_method` into the coordinates, tiled stop list and `extend`
+/// flag that should actually be written to the shading dictionary.
+///
+/// For [`SpreadMethod::Pad`] this is just the gradient's own coords/stops with
+/// `extend = true`, matching the PDF default. For [`SpreadMethod::Reflect`] and
+/// [`SpreadMethod::Repeat`] we instead widen the coords to cover `parent_bbox` and
+/// replicate the stop list once per period, so the existing `extend = false` shading
+/// already tiles correctly without any special-casing downstream.
+fn spread_coords_and_stops(
+    properties: &GradientProperties,
+    parent_bbox: &NonZeroRect,
+) -> (Vec<f32>, Vec<usvg::Stop>, bool) {
+    if properties.spread_method == SpreadMethod::Pad {
+        return (properties.coords.clone(), properties.stops.clone(), true);
+    }
+
+    let (periods_before, periods_after) = required_periods(properties, parent_bbox);
+    let coords = tiled_coords(properties, periods_before, periods_after);
+    let stops = tile_stops(
+        &properties.stops,
+        properties.spread_method,
+        periods_before,
+        periods_before + periods_after + 1,
+    );
+
+    (coords, stops, false)
+}
+
+/// Hard ceiling on periods tiled to either side of a spread gradient's `[0, 1]` range.
+///
+/// A focal radius very close to the outer radius (a valid `usvg` input) shrinks
+/// `radial_periods`' period span toward zero, and a bbox far from the gradient center
+/// then drives the period count toward infinity; `axial_periods` has the same failure
+/// mode for a near-degenerate gradient vector. Without a cap, `tiled_coords`/`tile_stops`
+/// would try to allocate and replicate the stop list that many times over. The cap is
+/// far above anything a real, well-conditioned gradient needs.
+const MAX_PERIODS: i32 = 10_000;
+
+/// How many extra gradient periods are needed before `t=0` and after `t=1` to fully
+/// cover `parent_bbox`, expressed in the same raw, pre-`gradientTransform` coordinate
+/// space as `properties.coords` (the unit square when `units` is `ObjectBoundingBox`,
+/// `parent_bbox` itself otherwise, with `gradientTransform` undone).
+fn required_periods(properties: &GradientProperties, parent_bbox: &NonZeroRect) -> (i32, i32) {
+    let corners = bbox_corners(properties, parent_bbox);
+
+    let (periods_before, periods_after) = match properties.shading_type {
+        ShadingType::Axial => axial_periods(&properties.coords, &corners),
+        ShadingType::Radial => radial_periods(&properties.coords, &corners),
+        _ => (0, 0),
+    };
+
+    (periods_before.min(MAX_PERIODS), periods_after.min(MAX_PERIODS))
+}
+
+fn bbox_corners(properties: &GradientProperties, parent_bbox: &NonZeroRect) -> [(f32, f32); 4] {
+    let corners = if properties.units == Units::ObjectBoundingBox {
+        [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)]
+    } else {
+        let (x0, y0) = (parent_bbox.x(), parent_bbox.y());
+        let (x1, y1) = (x0 + parent_bbox.width(), y0 + parent_bbox.height());
+        [(x0, y0), (x1, y0), (x0, y1), (x1, y1)]
+    };
+
+    // `corners` above live in bbox/user space, i.e. *after* `gradientTransform` has
+    // mapped the gradient's own raw coordinate space into it (see the `matrix` built in
+    // `create_shading_pattern`). `coords` are still expressed in that pre-transform raw
+    // space, so undo `gradientTransform` here to bring both into the same space before
+    // the period math below compares them.
+    match properties.transform.invert() {
+        Some(inverse) => corners.map(|(x, y)| inverse.apply(x, y)),
+        None => corners,
+    }
+}
+
+fn axial_periods(coords: &[f32], corners: &[(f32, f32); 4]) -> (i32, i32) {
+    let (x1, y1, x2, y2) = (coords[0], coords[1], coords[2], coords[3]);
+    let (dx, dy) = (x2 - x1, y2 - y1);
+    let len_sq = dx * dx + dy * dy;
+    if len_sq == 0.0 {
+        return (0, 0);
+    }
+
+    let mut min_t = 0.0_f32;
+    let mut max_t = 1.0_f32;
+    for &(cx, cy) in corners {
+        let t = ((cx - x1) * dx + (cy - y1) * dy) / len_sq;
+        min_t = min_t.min(t);
+        max_t = max_t.max(t);
+    }
+
+    let periods_before = (-min_t).max(0.0).ceil() as i32;
+    let periods_after = (max_t - 1.0).max(0.0).ceil() as i32;
+    (periods_before, periods_after)
+}
+
+fn radial_periods(coords: &[f32], corners: &[(f32, f32); 4]) -> (i32, i32) {
+    let (fr, cx, cy, r) = (coords[2], coords[3], coords[4], coords[5]);
+    if r <= 0.0 {
+        return (0, 0);
+    }
+
+    let max_dist = corners
+        .iter()
+        .fold(r, |acc, &(x, y)| acc.max(((x - cx).powi(2) + (y - cy).powi(2)).sqrt()));
+
+    // Each period covers a ring of width `r - fr` (PDF interpolates radius linearly
+    // from `fr` at t=0 to `r` at t=1), not `r` alone, so a non-zero focal radius must
+    // shrink the period span the same way `tiled_coords` widens it below.
+    let period_span = (r - fr).max(f32::EPSILON);
+    let periods_after = (((max_dist - fr) / period_span) - 1.0).max(0.0).ceil() as i32;
+    (0, periods_after)
+}
+
+/// Widen `properties.coords` so that `t` in `[0, 1]` spans
+/// `periods_before + periods_after + 1` periods, each as long as the original gradient.
+fn tiled_coords(properties: &GradientProperties, periods_before: i32, periods_after: i32) -> Vec<f32> {
+    match properties.shading_type {
+        ShadingType::Axial => {
+            let (x1, y1, x2, y2) = (
+                properties.coords[0],
+                properties.coords[1],
+                properties.coords[2],
+                properties.coords[3],
+            );
+            let (dx, dy) = (x2 - x1, y2 - y1);
+            vec![
+                x1 - periods_before as f32 * dx,
+                y1 - periods_before as f32 * dy,
+                x2 + periods_after as f32 * dx,
+                y2 + periods_after as f32 * dy,
+            ]
+        }
+        ShadingType::Radial => {
+            let total_periods = (periods_before + periods_after + 1) as f32;
+            let (fx, fy, fr, cx, cy, r) = (
+                properties.coords[0],
+                properties.coords[1],
+                properties.coords[2],
+                properties.coords[3],
+                properties.coords[4],
+                properties.coords[5],
+            );
+            // PDF interpolates the shading's radius linearly with `t` from `fr` (at
+            // t=0) to the tiled outer radius (at t=1). Scaling only `r` while leaving
+            // `fr` fixed would shrink every period's radius *range* by `fr`, so ring
+            // spacing would drift further from a clean multiple of `r` as more periods
+            // are tiled in. Scaling the range (`r - fr`) instead keeps each period
+            // spanning exactly the same radius span as the original single gradient.
+            vec![fx, fy, fr, cx, cy, fr + (r - fr) * total_periods]
+        }
+        _ => properties.coords.clone(),
+    }
+}
+
+/// Replicate `stops` once per period across `[0, 1]`, reversing every other copy for
+/// [`SpreadMethod::Reflect`] so adjacent periods mirror at their shared boundary.
+///
+/// Parity has to be measured relative to `periods_before` (the "home" period, i.e. the
+/// one covering the gradient's own `t` in `[0, 1]` with its stops in original order),
+/// not the absolute `period` index: when `periods_before` is odd, indexing off `period`
+/// alone would reverse the home period itself and put every mirror boundary one period
+/// off from where `tiled_coords` put the matching geometry.
+fn tile_stops(
+    stops: &[usvg::Stop],
+    spread_method: SpreadMethod,
+    periods_before: i32,
+    total_periods: i32,
+) -> Vec<usvg::Stop> {
+    let total_periods = total_periods.max(1);
+    let mut tiled = Vec::with_capacity(stops.len() * total_periods as usize);
+
+    for period in 0..total_periods {
+        let reversed =
+            spread_method == SpreadMethod::Reflect && (period - periods_before).rem_euclid(2) == 1;
+        let period_start = period as f32 / total_periods as f32;
+        let period_len = 1.0 / total_periods as f32;
+
+        let ordered: Box<dyn Iterator<Item = &usvg::Stop>> =
+            if reversed { Box::new(stops.iter().rev()) } else { Box::new(stops.iter()) };
+
+        for stop in ordered {
+            let local_offset = if reversed { 1.0 - stop.offset.get() } else { stop.offset.get() };
+            let mut new_stop = *stop;
+            new_stop.offset =
+                StopOffset::new((period_start + local_offset * period_len).clamp(0.0, 1.0))
+                    .unwrap();
+            tiled.push(new_stop);
+        }
+    }
+
+    tiled
+}
+
+/// Build a luminosity soft mask for a gradient with per-stop opacities and return the
+/// `ExtGState`'s `Ref`. The caller (not this function) is responsible for registering
+/// that `Ref` into the current frame's Resources via `ctx.deferrer.add_graphics_state`,
+/// since the same `Ref` may need registering again under a different content stream's
+/// Resources dictionary on a later cache hit.
 fn get_soft_mask(
     properties: &GradientProperties,
     parent_bbox: &NonZeroRect,
     writer: &mut PdfWriter,
     ctx: &mut Context,
-) -> Rc<String> {
+) -> Ref {
     ctx.deferrer.push();
     let x_object_id = ctx.alloc_ref();
     let shading_ref = ctx.alloc_ref();
@@ -155,14 +447,16 @@ fn get_soft_mask(
         },
     );
 
-    let shading_function_ref = get_function(&properties.stops, writer, ctx, true);
+    let (coords, stops, extend) = spread_coords_and_stops(properties, parent_bbox);
+
+    let shading_function_ref = get_function(&stops, writer, ctx, true);
     let mut shading = writer.shading(shading_ref);
     shading.shading_type(properties.shading_type);
     shading.color_space().d65_gray();
 
     shading.function(shading_function_ref);
-    shading.coords(properties.coords.iter().copied());
-    shading.extend([true, true]);
+    shading.coords(coords.iter().copied());
+    shading.extend([extend, extend]);
     shading.finish();
 
     let mut content = Content::new();
@@ -195,7 +489,7 @@ fn get_soft_mask(
         .group(x_object_id)
         .finish();
 
-    ctx.deferrer.add_graphics_state(gs_ref)
+    gs_ref
 }
 
 fn get_function(
@@ -208,6 +502,11 @@ fn get_function(
     // into no fill / plain fill, so there should be at least two stops
     debug_assert!(stops.len() > 1);
 
+    let cache_key = function_cache_key(stops, use_opacities);
+    if let Some(cached) = ctx.deferrer.cached_function(cache_key) {
+        return cached;
+    }
+
     let mut stops = stops.to_owned();
 
     // We manually pad the stops if necessary so that they are always in the range from 0-1
@@ -228,13 +527,36 @@ fn get_function(
     }
 
 
-    if use_opacities {
+    let reference = if use_opacities {
         let stops = stops.iter().map(|s| s.opacity_stops()).collect::<Vec<Stop<1>>>();
         function(&stops, writer, ctx)
-    }   else {
+    } else {
         let stops = stops.iter().map(|s| s.color_stops()).collect::<Vec<Stop<3>>>();
         function(&stops, writer, ctx)
+    };
+
+    ctx.deferrer.cache_function(cache_key, reference);
+    reference
+}
+
+/// A structural fingerprint of the (already spread-expanded) stops a gradient function
+/// is built from, so identical stop lists share one PDF function object.
+fn function_cache_key(stops: &[usvg::Stop], use_opacities: bool) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    use_opacities.hash(&mut hasher);
+
+    for stop in stops {
+        hash_f32(&mut hasher, stop.offset.get());
+        if use_opacities {
+            hash_f32(&mut hasher, stop.opacity.get());
+        } else {
+            for c in stop.color_stops().color {
+                hash_f32(&mut hasher, c);
+            }
+        }
     }
+
+    hasher.finish()
 }
 
 fn function<const COUNT: usize>(
@@ -305,3 +627,112 @@ fn get_function_range(count: usize) -> Vec<f32> {
     [0.0, 1.0].repeat(count)
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamp_focal_point_leaves_interior_point_untouched() {
+        let (fx, fy) = clamp_focal_point(1.0, 0.0, 0.0, 0.0, 0.0, 10.0);
+        assert_eq!((fx, fy), (1.0, 0.0));
+    }
+
+    #[test]
+    fn clamp_focal_point_pulls_exterior_point_inside_the_outer_circle() {
+        let (fx, fy) = clamp_focal_point(20.0, 0.0, 0.0, 0.0, 0.0, 10.0);
+        let dist = (fx * fx + fy * fy).sqrt();
+        assert!(dist < 10.0, "clamped focal point {dist} should land strictly inside r=10");
+        assert!(dist > 9.9, "clamped focal point {dist} should stay close to the boundary it hit");
+    }
+
+    #[test]
+    fn clamp_focal_point_respects_a_non_zero_focal_radius() {
+        // max_dist is (r - fr) = 1.0 here, so a focal point at distance 1.5 must be
+        // pulled in even though it is still well inside the outer circle itself.
+        let (fx, fy) = clamp_focal_point(1.5, 0.0, 1.0, 0.0, 0.0, 2.0);
+        let dist = (fx * fx + fy * fy).sqrt();
+        assert!(dist < 1.0, "clamped focal point {dist} should land inside r - fr = 1.0");
+    }
+
+    #[test]
+    fn axial_periods_covers_corners_outside_the_unit_segment() {
+        let coords = [0.0, 0.0, 1.0, 0.0];
+        let corners = [(-2.0, 0.0), (3.0, 0.0), (-2.0, 1.0), (3.0, 1.0)];
+        assert_eq!(axial_periods(&coords, &corners), (2, 2));
+    }
+
+    #[test]
+    fn axial_periods_needs_none_when_corners_already_fit() {
+        let coords = [0.0, 0.0, 1.0, 0.0];
+        let corners = [(0.2, 0.0), (0.8, 0.0), (0.2, 1.0), (0.8, 1.0)];
+        assert_eq!(axial_periods(&coords, &corners), (0, 0));
+    }
+
+    #[test]
+    fn radial_periods_ignores_nothing_when_focal_radius_is_zero() {
+        // coords layout: [fx, fy, fr, cx, cy, r]
+        let coords = [0.0, 0.0, 0.0, 0.0, 0.0, 10.0];
+        let corners = [(100.0, 0.0), (0.0, 100.0), (-100.0, 0.0), (0.0, -100.0)];
+        assert_eq!(radial_periods(&coords, &corners), (0, 9));
+    }
+
+    #[test]
+    fn radial_periods_accounts_for_a_non_zero_focal_radius() {
+        // cx=cy=0, r=10, fr=9: each period only spans a ring of width 1, so covering a
+        // corner 100 units out takes far more periods than sizing off `r` alone would.
+        let coords = [0.0, 0.0, 9.0, 0.0, 0.0, 10.0];
+        let corners = [(100.0, 0.0), (0.0, 100.0), (-100.0, 0.0), (0.0, -100.0)];
+        assert_eq!(radial_periods(&coords, &corners), (0, 90));
+    }
+
+    #[test]
+    fn required_periods_clamps_a_near_degenerate_focal_radius_blowup() {
+        // fr is a hair under r, so period_span is tiny; a bbox far from the gradient
+        // center would otherwise drive periods_after into the billions.
+        let properties = GradientProperties {
+            coords: vec![0.0, 0.0, 9.999_999, 0.0, 0.0, 10.0],
+            shading_type: ShadingType::Radial,
+            stops: Vec::new(),
+            transform: Transform::default(),
+            units: Units::UserSpaceOnUse,
+            spread_method: SpreadMethod::Repeat,
+        };
+        let parent_bbox = NonZeroRect::from_xywh(-1.0e6, -1.0e6, 2.0e6, 2.0e6).unwrap();
+
+        let (periods_before, periods_after) = required_periods(&properties, &parent_bbox);
+        assert_eq!(periods_before, 0);
+        assert!(periods_after <= MAX_PERIODS);
+    }
+
+    #[test]
+    fn tiled_coords_widens_the_radial_range_by_the_focal_to_outer_span() {
+        let properties = GradientProperties {
+            coords: vec![0.0, 0.0, 9.0, 0.0, 0.0, 10.0],
+            shading_type: ShadingType::Radial,
+            stops: Vec::new(),
+            transform: Transform::default(),
+            units: Units::ObjectBoundingBox,
+            spread_method: SpreadMethod::Repeat,
+        };
+
+        // total_periods = periods_before + periods_after + 1 = 0 + 90 + 1 = 91
+        let coords = tiled_coords(&properties, 0, 90);
+        assert_eq!(coords, vec![0.0, 0.0, 9.0, 0.0, 0.0, 9.0 + 1.0 * 91.0]);
+    }
+
+    #[test]
+    fn tiled_coords_widens_the_axial_segment_in_both_directions() {
+        let properties = GradientProperties {
+            coords: vec![0.0, 0.0, 1.0, 0.0],
+            shading_type: ShadingType::Axial,
+            stops: Vec::new(),
+            transform: Transform::default(),
+            units: Units::ObjectBoundingBox,
+            spread_method: SpreadMethod::Reflect,
+        };
+
+        let coords = tiled_coords(&properties, 2, 3);
+        assert_eq!(coords, vec![-2.0, 0.0, 4.0, 0.0]);
+    }
+}
+
@@ -1,182 +1,311 @@
+use crate::render::export::Export;
 use crate::render::group::create_x_object;
-use crate::util::helper::{ColorExt, NameExt, RectExt, TransformExt, SRGB};
+use crate::util::helper::{ColorExt, NameExt, RectExt, StopExt, TransformExt};
 use crate::util::{Context, RenderContext};
-use pdf_writer::types::ColorSpaceOperand::Pattern;
-use pdf_writer::types::{
-    ColorSpaceOperand, LineCapStyle, LineJoinStyle, PaintType, TilingType,
-};
+use pdf_writer::types::{PaintType, TilingType};
 use pdf_writer::{Content, Finish, PdfWriter};
 
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use std::rc::Rc;
 
-use usvg::{Node, Stroke, Units};
-use usvg::{Fill, NodeKind, Transform};
-use usvg::{FillRule, LineCap, LineJoin, Paint, PathSegment, Visibility};
+use usvg::{Fill, Node, NodeKind, Paint, PathSegment, Stroke, Transform};
 use usvg::utils::view_box_to_transform;
 
-pub(crate) fn render(
-    path: &usvg::Path,
-    node: &Node,
+/// Replay a path's segments into whatever backend is being written to.
+///
+/// This is the one piece of tree-walking logic shared between the pattern/gradient
+/// branch in `write::path` and [`crate::render::export::write_path`]; everything backend-specific
+/// goes through [`Export`] instead.
+pub fn draw_path<E: Export>(path_data: impl Iterator<Item = PathSegment>, exporter: &mut E) {
+    for operation in path_data {
+        match operation {
+            PathSegment::MoveTo { x, y } => exporter.move_to(x as f32, y as f32),
+            PathSegment::LineTo { x, y } => exporter.line_to(x as f32, y as f32),
+            PathSegment::CurveTo { x1, y1, x2, y2, x, y } => exporter
+                .cubic_to(x1 as f32, y1 as f32, x2 as f32, y2 as f32, x as f32, y as f32),
+            PathSegment::ClosePath => exporter.close_path(),
+        };
+    }
+}
+
+pub(crate) fn set_stroke<E: Export>(stroke: &Stroke, exporter: &mut E) {
+    exporter.set_line_width(stroke.width.get() as f32);
+    exporter.set_miter_limit(stroke.miterlimit.get() as f32);
+    exporter.set_line_cap(stroke.linecap);
+    exporter.set_line_join(stroke.linejoin);
+
+    if let Some(dasharray) = &stroke.dasharray {
+        exporter.set_dash_pattern(
+            dasharray.iter().map(|&x| x as f32).collect(),
+            stroke.dashoffset,
+        );
+    }
+}
+
+pub(crate) fn create_pattern(
+    pattern: Rc<usvg::Pattern>,
+    parent: &Node,
     writer: &mut PdfWriter,
-    content: &mut Content,
     ctx: &mut Context,
-) {
-    if path.visibility != Visibility::Visible {
-        return;
-    }
+) -> Rc<String> {
+    // The same `<pattern>` *definition* is frequently painted more than once at the same
+    // position (e.g. shared via multiple `fill="url(#p)"` references with no extra
+    // transform in between), and distinct definitions with identical content are common
+    // too (machine-generated SVGs love to repeat a `<pattern>` under a new id); reuse the
+    // already-written tiling pattern in either case instead of re-walking its content and
+    // re-emitting an identical object. The cache holds the pattern's `Ref`, not its
+    // resource name: a name is only meaningful within the Resources dictionary of
+    // whatever content stream is currently being built, so it has to be re-registered via
+    // `ctx.deferrer.add_pattern` on every hit, not just the first time the pattern is
+    // emitted.
+    let cache_key = pattern_cache_key(&pattern, &ctx.context_frame.transform());
 
-    ctx.context_frame.push();
-    ctx.context_frame.append_transform(&path.transform);
+    let pattern_id = match ctx.deferrer.cached_tiling_pattern(cache_key) {
+        Some(id) => id,
+        None => {
+            let pattern_id = ctx.alloc_ref();
+            ctx.deferrer.push();
 
-    content.save_state();
-    content.transform(ctx.context_frame.transform().as_array());
-    content.set_fill_color_space(ColorSpaceOperand::Named(SRGB));
-    content.set_stroke_color_space(ColorSpaceOperand::Named(SRGB));
+            match *pattern.root.borrow() {
+                NodeKind::Group(ref group) => {
+                    let mut parent_transform = ctx.context_frame.transform();
+                    parent_transform.append(&pattern.transform);
+                    ctx.context_frame.push();
+                    ctx.context_frame.set_transform(Transform::default());
 
-    let stroke_opacity = path.stroke.as_ref().map(|s| s.opacity.get() as f32);
-    let fill_opacity = path.fill.as_ref().map(|f| f.opacity.get() as f32);
+                    if let Some(viewbox) = pattern.view_box {
+                        ctx.context_frame.append_transform(&view_box_to_transform(viewbox.rect, viewbox.aspect, pattern.rect.size()))
+                    }
 
-    if stroke_opacity.unwrap_or(1.0) != 1.0 || fill_opacity.unwrap_or(1.0) != 1.0 {
-        let name = ctx.deferrer.add_opacity(stroke_opacity, fill_opacity);
-        content.set_parameters(name.as_name());
-    }
+                    ctx.context_frame.set_render_context(RenderContext::Pattern);
+                    let (x_object_name, _) = create_x_object(&pattern.root, group, writer, ctx);
 
-    if let Some(stroke) = &path.stroke {
-        set_stroke(stroke, content);
-    }
 
-    if let Some(fill) = &path.fill {
-        set_fill(fill, &node.parent().unwrap(), content, writer, ctx);
-    }
+                    let mut pattern_content = Content::new();
+                    pattern_content.x_object(x_object_name.as_name());
+                    let pattern_content_stream = pattern_content.finish();
+
+                    let mut tiling_pattern =
+                        writer.tiling_pattern(pattern_id, &pattern_content_stream);
 
-    draw_path(path.data.segments(), content);
-    finish_path(path.stroke.as_ref(), path.fill.as_ref(), content);
+                    let mut resources = tiling_pattern.resources();
+                    ctx.deferrer.pop(&mut resources);
+                    resources.finish();
+                    let final_bbox = pattern.rect.as_pdf_rect(&Transform::default());
 
-    content.restore_state();
-    ctx.context_frame.pop();
+                    tiling_pattern
+                        .tiling_type(TilingType::ConstantSpacing)
+                        .paint_type(PaintType::Colored)
+                        .bbox(final_bbox)
+                        .matrix(parent_transform.as_array())
+                        .x_step(final_bbox.x2 - final_bbox.x1)
+                        .y_step(final_bbox.y2 - final_bbox.y1);
+
+                    ctx.context_frame.pop();
+                }
+                _ => unreachable!(),
+            }
+
+            ctx.deferrer.cache_tiling_pattern(cache_key, pattern_id);
+            pattern_id
+        }
+    };
+
+    ctx.deferrer.add_pattern(pattern_id)
 }
 
-pub fn draw_path(path_data: impl Iterator<Item = PathSegment>, content: &mut Content) {
-    for operation in path_data {
-        match operation {
-            PathSegment::MoveTo { x, y } => content.move_to(x as f32, y as f32),
-            PathSegment::LineTo { x, y } => content.line_to(x as f32, y as f32),
-            PathSegment::CurveTo { x1, y1, x2, y2, x, y } => content
-                .cubic_to(x1 as f32, y1 as f32, x2 as f32, y2 as f32, x as f32, y as f32),
-            PathSegment::ClosePath => content.close_path(),
-        };
+fn hash_transform(transform: &Transform) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hash_transform_into(transform, &mut hasher);
+    hasher.finish()
+}
+
+fn hash_transform_into<H: Hasher>(transform: &Transform, hasher: &mut H) {
+    for v in transform.to_pdf_transform() {
+        v.to_bits().hash(hasher);
     }
 }
 
-fn finish_path(stroke: Option<&Stroke>, fill: Option<&Fill>, content: &mut Content) {
-    match (stroke, fill.map(|f| f.rule)) {
-        (Some(_), Some(FillRule::NonZero)) => content.fill_nonzero_and_stroke(),
-        (Some(_), Some(FillRule::EvenOdd)) => content.fill_even_odd_and_stroke(),
-        (None, Some(FillRule::NonZero)) => content.fill_nonzero(),
-        (None, Some(FillRule::EvenOdd)) => content.fill_even_odd(),
-        (Some(_), None) => content.stroke(),
-        (None, None) => content.end_path(),
-    };
+fn hash_rect<H: Hasher>(rect: &usvg::NonZeroRect, hasher: &mut H) {
+    rect.x().to_bits().hash(hasher);
+    rect.y().to_bits().hash(hasher);
+    rect.width().to_bits().hash(hasher);
+    rect.height().to_bits().hash(hasher);
 }
 
-fn set_stroke(stroke: &Stroke, content: &mut Content) {
-    content.set_line_width(stroke.width.get() as f32);
-    content.set_miter_limit(stroke.miterlimit.get() as f32);
+/// A cache key for [`create_pattern`]: a structural fingerprint of the pattern's
+/// definition (its own `transform`/`viewBox`/`rect` and, to the extent covered below, the
+/// content it draws) combined with the accumulated transform it is painted under. Two
+/// calls with the same key would write byte-identical tiling pattern objects, so the
+/// second one can just reuse the first's `Ref`.
+///
+/// `<pattern>` content made only of groups and solid/gradient-filled paths — by far the
+/// common case — is hashed field-by-field, so two distinct `<pattern>` definitions with
+/// identical content dedup even though they are different `Rc`s. Content this function
+/// doesn't know how to fingerprint precisely (nested `<pattern>` paint, `<text>`/`<image>`
+/// children, clip paths, masks) falls back to that `Rc`'s pointer identity instead of
+/// being silently left out of the hash, so two *different* patterns can never collide
+/// onto the same cache entry even where full structural coverage isn't implemented.
+fn pattern_cache_key(pattern: &Rc<usvg::Pattern>, accumulated_transform: &Transform) -> (usize, u64) {
+    let mut hasher = DefaultHasher::new();
+    hash_transform_into(&pattern.transform, &mut hasher);
+    hash_rect(&pattern.rect, &mut hasher);
 
-    match stroke.linecap {
-        LineCap::Butt => content.set_line_cap(LineCapStyle::ButtCap),
-        LineCap::Round => content.set_line_cap(LineCapStyle::RoundCap),
-        LineCap::Square => content.set_line_cap(LineCapStyle::ProjectingSquareCap),
-    };
+    if let Some(view_box) = pattern.view_box {
+        hash_rect(&view_box.rect, &mut hasher);
+        format!("{:?}", view_box.aspect).hash(&mut hasher);
+    }
 
-    match stroke.linejoin {
-        LineJoin::Miter => content.set_line_join(LineJoinStyle::MiterJoin),
-        LineJoin::Round => content.set_line_join(LineJoinStyle::RoundJoin),
-        LineJoin::Bevel => content.set_line_join(LineJoinStyle::BevelJoin),
-    };
+    let mut fully_covered = true;
+    hash_node(&pattern.root, &mut hasher, &mut fully_covered);
+    let content_hash = hasher.finish();
 
-    if let Some(dasharray) = &stroke.dasharray {
-        content.set_dash_pattern(dasharray.iter().map(|&x| x as f32), stroke.dashoffset);
-    }
+    let identity = if fully_covered {
+        content_hash as usize
+    } else {
+        (content_hash as usize) ^ (Rc::as_ptr(pattern) as usize)
+    };
 
-    match &stroke.paint {
-        Paint::Color(c) => {
-            content.set_stroke_color(c.as_array());
-        }
-        Paint::Pattern(_) => todo!(),
-        _ => {} //_ => todo!(),
-    }
+    (identity, hash_transform(accumulated_transform))
 }
 
-fn set_fill(
-    fill: &Fill,
-    parent: &Node,
-    content: &mut Content,
-    writer: &mut PdfWriter,
-    ctx: &mut Context,
-) {
-    let paint = &fill.paint;
+fn hash_node<H: Hasher>(node: &Node, hasher: &mut H, fully_covered: &mut bool) {
+    match *node.borrow() {
+        NodeKind::Group(ref group) => {
+            if group.clip_path.is_some() || group.mask.is_some() {
+                *fully_covered = false;
+            }
 
-    match paint {
-        Paint::Color(c) => {
-            content.set_fill_color(c.as_array());
+            0u8.hash(hasher);
+            hash_transform_into(&group.transform, hasher);
+            group.opacity.get().to_bits().hash(hasher);
+            format!("{:?}", group.blend_mode).hash(hasher);
+
+            for child in node.children() {
+                hash_node(&child, hasher, fully_covered);
+            }
         }
-        Paint::Pattern(p) => {
-            let pattern_name = create_pattern(p.clone(), parent, writer, ctx);
-            content.set_fill_color_space(Pattern);
-            content.set_fill_pattern(None, pattern_name.as_name());
+        NodeKind::Path(ref path) => {
+            1u8.hash(hasher);
+            hash_transform_into(&path.transform, hasher);
+            format!("{:?}", path.visibility).hash(hasher);
+            hash_path_data(path, hasher);
+            hash_fill(&path.fill, hasher, fully_covered);
+            hash_stroke(&path.stroke, hasher, fully_covered);
+        }
+        _ => {
+            // `<text>`/`<image>` content inside a `<pattern>` isn't hashed structurally
+            // here; `pattern_cache_key` falls back to pointer identity for these rather
+            // than risk merging two visually different patterns under the same cache key.
+            *fully_covered = false;
         }
-        _ => {}
     }
 }
 
-fn create_pattern(
-    pattern: Rc<usvg::Pattern>,
-    parent: &Node,
-    writer: &mut PdfWriter,
-    ctx: &mut Context,
-) -> String {
-    let (pattern_name, pattern_id) = ctx.deferrer.add_pattern();
-    ctx.deferrer.push();
-
-    match *pattern.root.borrow() {
-        NodeKind::Group(ref group) => {
-            let mut parent_transform = ctx.context_frame.transform();
-            parent_transform.append(&pattern.transform);
-            ctx.context_frame.push();
-            ctx.context_frame.set_transform(Transform::default());
-
-            if let Some(viewbox) = pattern.view_box {
-                ctx.context_frame.append_transform(&view_box_to_transform(viewbox.rect, viewbox.aspect, pattern.rect.size()))
+fn hash_path_data<H: Hasher>(path: &usvg::Path, hasher: &mut H) {
+    for segment in path.data.segments() {
+        match segment {
+            PathSegment::MoveTo { x, y } => {
+                0u8.hash(hasher);
+                x.to_bits().hash(hasher);
+                y.to_bits().hash(hasher);
             }
+            PathSegment::LineTo { x, y } => {
+                1u8.hash(hasher);
+                x.to_bits().hash(hasher);
+                y.to_bits().hash(hasher);
+            }
+            PathSegment::CurveTo { x1, y1, x2, y2, x, y } => {
+                2u8.hash(hasher);
+                for v in [x1, y1, x2, y2, x, y] {
+                    v.to_bits().hash(hasher);
+                }
+            }
+            PathSegment::ClosePath => 3u8.hash(hasher),
+        }
+    }
+}
 
-            ctx.context_frame.set_render_context(RenderContext::Pattern);
-            let (x_object_name, _) = create_x_object(&pattern.root, group, writer, ctx);
-
+fn hash_fill<H: Hasher>(fill: &Option<Fill>, hasher: &mut H, fully_covered: &mut bool) {
+    match fill {
+        Some(fill) => {
+            1u8.hash(hasher);
+            hash_paint(&fill.paint, hasher, fully_covered);
+            fill.opacity.get().to_bits().hash(hasher);
+            format!("{:?}", fill.rule).hash(hasher);
+        }
+        None => 0u8.hash(hasher),
+    }
+}
 
-            let mut pattern_content = Content::new();
-            pattern_content.x_object(x_object_name.as_name());
-            let pattern_content_stream = pattern_content.finish();
+fn hash_stroke<H: Hasher>(stroke: &Option<Stroke>, hasher: &mut H, fully_covered: &mut bool) {
+    match stroke {
+        Some(stroke) => {
+            1u8.hash(hasher);
+            hash_paint(&stroke.paint, hasher, fully_covered);
+            stroke.width.get().to_bits().hash(hasher);
+            stroke.miterlimit.get().to_bits().hash(hasher);
+            format!("{:?}", stroke.linecap).hash(hasher);
+            format!("{:?}", stroke.linejoin).hash(hasher);
+            stroke.opacity.get().to_bits().hash(hasher);
 
-            let mut tiling_pattern =
-                writer.tiling_pattern(pattern_id, &pattern_content_stream);
+            if let Some(dasharray) = &stroke.dasharray {
+                for v in dasharray {
+                    v.to_bits().hash(hasher);
+                }
+            }
 
-            let mut resources = tiling_pattern.resources();
-            ctx.deferrer.pop(&mut resources);
-            resources.finish();
-            let final_bbox = pattern.rect.as_pdf_rect(&Transform::default());
+            stroke.dashoffset.to_bits().hash(hasher);
+        }
+        None => 0u8.hash(hasher),
+    }
+}
 
-            tiling_pattern
-                .tiling_type(TilingType::ConstantSpacing)
-                .paint_type(PaintType::Colored)
-                .bbox(final_bbox)
-                .matrix(parent_transform.as_array())
-                .x_step(final_bbox.x2 - final_bbox.x1)
-                .y_step(final_bbox.y2 - final_bbox.y1);
+fn hash_paint<H: Hasher>(paint: &Paint, hasher: &mut H, fully_covered: &mut bool) {
+    match paint {
+        Paint::Color(c) => {
+            0u8.hash(hasher);
+            for v in c.as_array() {
+                v.to_bits().hash(hasher);
+            }
+        }
+        Paint::LinearGradient(g) => {
+            1u8.hash(hasher);
+            hash_transform_into(&g.transform, hasher);
+            format!("{:?}", g.spread_method).hash(hasher);
+            format!("{:?}", g.units).hash(hasher);
+            for v in [g.x1, g.y1, g.x2, g.y2] {
+                v.to_bits().hash(hasher);
+            }
+            hash_stops(&g.stops, hasher);
+        }
+        Paint::RadialGradient(g) => {
+            2u8.hash(hasher);
+            hash_transform_into(&g.transform, hasher);
+            format!("{:?}", g.spread_method).hash(hasher);
+            format!("{:?}", g.units).hash(hasher);
+            for v in [g.cx, g.cy, g.fx, g.fy, g.r.get(), g.fr.get()] {
+                v.to_bits().hash(hasher);
+            }
+            hash_stops(&g.stops, hasher);
+        }
+        Paint::Pattern(_) => {
+            // A `<pattern>` nested inside another pattern's fill/stroke is rare; fall
+            // back to pointer identity for the outer pattern rather than recursing
+            // indefinitely or risking a collision between two different nested patterns.
+            3u8.hash(hasher);
+            *fully_covered = false;
+        }
+    }
+}
 
-            ctx.context_frame.pop();
-            pattern_name
+fn hash_stops<H: Hasher>(stops: &[usvg::Stop], hasher: &mut H) {
+    for stop in stops {
+        stop.offset.get().to_bits().hash(hasher);
+        stop.opacity.get().to_bits().hash(hasher);
+        for c in stop.color_stops().color {
+            c.to_bits().hash(hasher);
         }
-        _ => unreachable!(),
     }
 }
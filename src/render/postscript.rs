@@ -0,0 +1,93 @@
+use usvg::{FillRule, LineCap, LineJoin};
+
+use crate::render::export::Export;
+
+/// Accumulates PostScript operators for a single page, to be wrapped in a DSC prologue
+/// (`%!PS-Adobe-3.0` / `%!PS-Adobe-3.0 EPSF-3.0`) by the caller once the scene is drawn.
+///
+/// This is the PostScript counterpart to writing into a `pdf_writer::Content` stream:
+/// `render::path` emits the same sequence of `Export` calls regardless of which of the
+/// two this is.
+#[derive(Default)]
+pub struct PostScriptWriter {
+    buf: String,
+}
+
+impl PostScriptWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn finish(self) -> String {
+        self.buf
+    }
+}
+
+impl Export for PostScriptWriter {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.buf.push_str(&format!("{x} {y} moveto\n"));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.buf.push_str(&format!("{x} {y} lineto\n"));
+    }
+
+    fn cubic_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        self.buf.push_str(&format!("{x1} {y1} {x2} {y2} {x} {y} curveto\n"));
+    }
+
+    fn close_path(&mut self) {
+        self.buf.push_str("closepath\n");
+    }
+
+    fn set_line_width(&mut self, width: f32) {
+        self.buf.push_str(&format!("{width} setlinewidth\n"));
+    }
+
+    fn set_miter_limit(&mut self, limit: f32) {
+        self.buf.push_str(&format!("{limit} setmiterlimit\n"));
+    }
+
+    fn set_line_cap(&mut self, cap: LineCap) {
+        let cap = match cap {
+            LineCap::Butt => 0,
+            LineCap::Round => 1,
+            LineCap::Square => 2,
+        };
+        self.buf.push_str(&format!("{cap} setlinecap\n"));
+    }
+
+    fn set_line_join(&mut self, join: LineJoin) {
+        let join = match join {
+            LineJoin::Miter => 0,
+            LineJoin::Round => 1,
+            LineJoin::Bevel => 2,
+        };
+        self.buf.push_str(&format!("{join} setlinejoin\n"));
+    }
+
+    fn set_stroke_color(&mut self, rgb: [f32; 3]) {
+        self.buf.push_str(&format!("{} {} {} setrgbcolor\n", rgb[0], rgb[1], rgb[2]));
+    }
+
+    fn set_fill_color(&mut self, rgb: [f32; 3]) {
+        self.buf.push_str(&format!("{} {} {} setrgbcolor\n", rgb[0], rgb[1], rgb[2]));
+    }
+
+    fn set_dash_pattern(&mut self, dasharray: Vec<f32>, dashoffset: f32) {
+        let entries = dasharray.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" ");
+        self.buf.push_str(&format!("[{entries}] {dashoffset} setdash\n"));
+    }
+
+    fn finish_path(&mut self, stroke: bool, fill: Option<FillRule>) {
+        // PostScript's `fill` uses the nonzero rule; `eofill` is used for even-odd.
+        match (stroke, fill) {
+            (true, Some(FillRule::NonZero)) => self.buf.push_str("gsave fill grestore stroke\n"),
+            (true, Some(FillRule::EvenOdd)) => self.buf.push_str("gsave eofill grestore stroke\n"),
+            (false, Some(FillRule::NonZero)) => self.buf.push_str("fill\n"),
+            (false, Some(FillRule::EvenOdd)) => self.buf.push_str("eofill\n"),
+            (true, None) => self.buf.push_str("stroke\n"),
+            (false, None) => self.buf.push_str("newpath\n"),
+        }
+    }
+}
@@ -1,7 +1,12 @@
-use crate::util::{Context, TransformExt};
+use std::rc::Rc;
+
+use crate::render::path::draw_path;
+use crate::util::helper::{NameExt, RectExt, TransformExt};
+use crate::util::Context;
 use crate::write::render::Render;
-use pdf_writer::{Content, PdfWriter};
-use usvg::{Node, Transform};
+use pdf_writer::types::{BlendMode as PdfBlendMode, MaskType as PdfMaskType};
+use pdf_writer::{Content, Filter, Finish, PdfWriter, Ref};
+use usvg::{BlendMode, FillRule, MaskType, Node, NodeKind};
 
 use super::render::node_to_stream;
 
@@ -13,12 +18,179 @@ impl Render for usvg::Group {
         content: &mut Content,
         ctx: &mut Context,
     ) {
+        ctx.context_frame.push();
+        ctx.context_frame.append_transform(&self.transform);
 
         content.save_state();
         content.transform(self.transform.get_transform());
 
-        node_to_stream(node, writer, ctx, content);
+        if let Some(clip_path) = &self.clip_path {
+            apply_clip_path(clip_path, content);
+        }
+
+        if self.opacity.get() < 1.0 || self.mask.is_some() || self.blend_mode != BlendMode::Normal {
+            draw_as_transparency_group(self, node, writer, content, ctx);
+        } else {
+            node_to_stream(node, writer, ctx, content);
+        }
 
         content.restore_state();
+        ctx.context_frame.pop();
+    }
+}
+
+/// Intersect the current clip region with `clip_path`'s geometry (and, recursively,
+/// whatever clip path it is itself clipped by), the way nested `clip-path` references
+/// chain in SVG.
+fn apply_clip_path(clip_path: &Rc<usvg::ClipPath>, content: &mut Content) {
+    if let Some(parent) = &clip_path.clip_path {
+        apply_clip_path(parent, content);
+    }
+
+    // No `save_state()` here: `Group::render` already brackets the whole group in one
+    // outer `q`/`Q` pair, and a clip (once intersected) stays in effect for the rest of
+    // that pair regardless of later `cm`s. So the positioning transform below is undone
+    // with its inverse rather than a nested `q`/`Q`, leaving the clip itself active.
+    content.transform(clip_path.transform.as_array());
+
+    let mut clip_rule = FillRule::NonZero;
+    for child in clip_path.root.children() {
+        if let NodeKind::Path(ref path) = *child.borrow() {
+            clip_rule = path.fill.as_ref().map(|f| f.rule).unwrap_or(FillRule::NonZero);
+            draw_path(path.data.segments(), content);
+        }
+    }
+
+    match clip_rule {
+        FillRule::NonZero => content.clip_nonzero(),
+        FillRule::EvenOdd => content.clip_even_odd(),
+    };
+    content.end_path();
+
+    if let Some(inverse) = clip_path.transform.invert() {
+        content.transform(inverse.as_array());
+    }
+}
+
+/// Render `group`'s children into a transparency group XObject and paint that XObject
+/// back into `content` under an `ExtGState` carrying the group's constant alpha,
+/// `mix-blend-mode`, and/or soft mask (luminosity or alpha, per the mask's own kind).
+///
+/// The XObject is isolated exactly when the group's `isolate` flag is set. This is how
+/// a PDF interpreter establishes compositing for an `<svg>` group that has `opacity`,
+/// `mask`, or a non-`normal` `mix-blend-mode` set, since none
+/// of those have a direct per-operator equivalent the way fill/stroke alpha does.
+fn draw_as_transparency_group(
+    group: &usvg::Group,
+    node: &Node,
+    writer: &mut PdfWriter,
+    content: &mut Content,
+    ctx: &mut Context,
+) {
+    ctx.deferrer.push();
+    let x_object_id = ctx.alloc_ref();
+    let bbox = ctx.get_rect().to_pdf_rect();
+
+    let mut group_content = Content::new();
+    node_to_stream(node, writer, ctx, &mut group_content);
+    let group_content_stream = ctx.finish_content(group_content);
+
+    let mut x_object = writer.form_xobject(x_object_id, &group_content_stream);
+    ctx.deferrer.pop(&mut x_object.resources());
+
+    x_object.group().transparency().isolated(group.isolate).knockout(false).color_space().srgb();
+
+    if ctx.options.compress {
+        x_object.filter(Filter::FlateDecode);
+    }
+
+    x_object.bbox(bbox);
+    x_object.finish();
+
+    let soft_mask = group.mask.as_ref().map(|mask| create_mask_group(mask, writer, ctx));
+
+    let gs_ref = ctx.alloc_ref();
+    let mut gs = writer.ext_graphics(gs_ref);
+
+    if group.opacity.get() < 1.0 {
+        let opacity = group.opacity.get() as f32;
+        gs.non_stroking_alpha(opacity).stroking_alpha(opacity);
+    }
+
+    if group.blend_mode != BlendMode::Normal {
+        gs.blend_mode(pdf_blend_mode(group.blend_mode));
+    }
+
+    if let Some((mask_id, mask_kind)) = soft_mask {
+        gs.soft_mask().subtype(pdf_mask_type(mask_kind)).group(mask_id).finish();
+    }
+
+    gs.finish();
+
+    let gs_name = ctx.deferrer.add_graphics_state(gs_ref);
+    let x_object_name = ctx.deferrer.add_x_object(x_object_id);
+
+    content.set_parameters(gs_name.as_name());
+    content.x_object(x_object_name.as_name());
+}
+
+fn create_mask_group(
+    mask: &Rc<usvg::Mask>,
+    writer: &mut PdfWriter,
+    ctx: &mut Context,
+) -> (Ref, MaskType) {
+    ctx.deferrer.push();
+    let x_object_id = ctx.alloc_ref();
+    let bbox = ctx.get_rect().to_pdf_rect();
+
+    let mut mask_content = Content::new();
+    node_to_stream(&mask.root, writer, ctx, &mut mask_content);
+    let mask_content_stream = ctx.finish_content(mask_content);
+
+    let mut x_object = writer.form_xobject(x_object_id, &mask_content_stream);
+    ctx.deferrer.pop(&mut x_object.resources());
+
+    x_object.group().transparency().isolated(true).knockout(false).color_space().d65_gray();
+
+    if ctx.options.compress {
+        x_object.filter(Filter::FlateDecode);
+    }
+
+    x_object.bbox(bbox);
+    x_object.finish();
+
+    (x_object_id, mask.kind)
+}
+
+/// Map an SVG `<mask>`'s `mask-type` to the PDF soft mask subtype that reproduces it:
+/// `luminance` derives alpha from the group's rendered luminosity, `alpha` uses its
+/// alpha channel directly.
+fn pdf_mask_type(kind: MaskType) -> PdfMaskType {
+    match kind {
+        MaskType::Luminance => PdfMaskType::Luminosity,
+        MaskType::Alpha => PdfMaskType::Alpha,
+    }
+}
+
+/// Map an SVG `mix-blend-mode` to its identically-named PDF blend mode; the two
+/// enumerations correspond 1:1.
+fn pdf_blend_mode(mode: BlendMode) -> PdfBlendMode {
+    match mode {
+        BlendMode::Normal => PdfBlendMode::Normal,
+        BlendMode::Multiply => PdfBlendMode::Multiply,
+        BlendMode::Screen => PdfBlendMode::Screen,
+        BlendMode::Overlay => PdfBlendMode::Overlay,
+        BlendMode::Darken => PdfBlendMode::Darken,
+        BlendMode::Lighten => PdfBlendMode::Lighten,
+        BlendMode::ColorDodge => PdfBlendMode::ColorDodge,
+        BlendMode::ColorBurn => PdfBlendMode::ColorBurn,
+        BlendMode::HardLight => PdfBlendMode::HardLight,
+        BlendMode::SoftLight => PdfBlendMode::SoftLight,
+        BlendMode::Difference => PdfBlendMode::Difference,
+        BlendMode::Exclusion => PdfBlendMode::Exclusion,
+        BlendMode::Hue => PdfBlendMode::Hue,
+        BlendMode::Saturation => PdfBlendMode::Saturation,
+        BlendMode::Color => PdfBlendMode::Color,
+        BlendMode::Luminosity => PdfBlendMode::Luminosity,
     }
 }
@@ -1,12 +1,17 @@
+use crate::render::export::{write_path, Export};
+use crate::render::gradient;
+use crate::render::path::{create_pattern, draw_path, set_stroke};
 use crate::util::Context;
 use crate::util::TransformExt;
 use crate::write::render::Render;
-use pdf_writer::types::{LineCapStyle, LineJoinStyle, ColorSpaceOperand};
+use pdf_writer::types::ColorSpaceOperand::Pattern;
+use pdf_writer::types::ColorSpaceOperand;
 use pdf_writer::{Content, PdfWriter};
 use usvg::Fill;
 use usvg::Stroke;
-use usvg::{FillRule, LineCap, LineJoin, Node, Paint, PathSegment, Visibility};
+use usvg::{Node, NonZeroRect, Paint, Visibility};
 use crate::util::RgbColor;
+use crate::util::helper::NameExt;
 
 impl Render for usvg::Path {
     fn render(
@@ -20,6 +25,9 @@ impl Render for usvg::Path {
             return;
         }
 
+        ctx.context_frame.push();
+        ctx.context_frame.append_transform(&self.transform);
+
         content.save_state();
         content.transform(self.transform.get_transform());
 
@@ -27,84 +35,109 @@ impl Render for usvg::Path {
         content.set_fill_color_space(ColorSpaceOperand::DeviceRgb);
         content.set_stroke_color_space(ColorSpaceOperand::DeviceRgb);
 
-        if let Some(stroke) = &self.stroke {
-            set_stroke(stroke, content);
-        }
-
-        if let Some(fill) = &self.fill {
-            set_fill(fill, content);
+        if is_plain_paint(self.stroke.as_ref().map(|s| &s.paint))
+            && is_plain_paint(self.fill.as_ref().map(|f| &f.paint))
+        {
+            // Neither paint needs a PDF-specific pattern/shading resource, so this path
+            // can be driven through the shared `write_path` helper instead of
+            // re-resolving solid-color stroke/fill state a second time.
+            write_path(self, content);
+        } else {
+            if let Some(stroke) = &self.stroke {
+                set_stroke(stroke, content);
+                set_stroke_paint(stroke, node, content, writer, ctx);
+            }
+
+            if let Some(fill) = &self.fill {
+                set_fill(fill, node, content, writer, ctx);
+            }
+
+            draw_path(self.data.segments(), content);
+            content.finish_path(self.stroke.is_some(), self.fill.as_ref().map(|f| f.rule));
         }
 
-        draw_path(self.data.segments(), content);
-        finish_path(self.stroke.as_ref(), self.fill.as_ref(), content);
-
         content.restore_state();
+        ctx.context_frame.pop();
     }
 }
 
-fn draw_path(path_data: impl Iterator<Item = PathSegment>, content: &mut Content) {
-    for operation in path_data {
-        match operation {
-            PathSegment::MoveTo { x, y } => content.move_to(x as f32, y as f32),
-            PathSegment::LineTo { x, y } => content.line_to(x as f32, y as f32),
-            PathSegment::CurveTo { x1, y1, x2, y2, x, y } => content
-                .cubic_to(x1 as f32, y1 as f32, x2 as f32, y2 as f32, x as f32, y as f32),
-            PathSegment::ClosePath => content.close_path(),
-        };
-    }
-}
-
-fn finish_path(stroke: Option<&Stroke>, fill: Option<&Fill>, content: &mut Content) {
-    match (stroke, fill.map(|f| f.rule)) {
-        (Some(_), Some(FillRule::NonZero)) => content.fill_nonzero_and_stroke(),
-        (Some(_), Some(FillRule::EvenOdd)) => content.fill_even_odd_and_stroke(),
-        (None, Some(FillRule::NonZero)) => content.fill_nonzero(),
-        (None, Some(FillRule::EvenOdd)) => content.fill_even_odd(),
-        (Some(_), _) => content.stroke(),
-        (None, _) => content.end_path(),
-    };
-}
-
-fn set_stroke(stroke: &Stroke, content: &mut Content) {
-    content.set_line_width(stroke.width.get() as f32);
-    content.set_miter_limit(stroke.miterlimit.get() as f32);
-
-    match stroke.linecap {
-        LineCap::Butt => content.set_line_cap(LineCapStyle::ButtCap),
-        LineCap::Round => content.set_line_cap(LineCapStyle::RoundCap),
-        LineCap::Square => {
-            content.set_line_cap(LineCapStyle::ProjectingSquareCap)
-        }
-    };
-
-    match stroke.linejoin {
-        LineJoin::Miter => content.set_line_join(LineJoinStyle::MiterJoin),
-        LineJoin::Round => content.set_line_join(LineJoinStyle::RoundJoin),
-        LineJoin::Bevel => content.set_line_join(LineJoinStyle::BevelJoin),
-    };
-
-    if let Some(dasharray) = &stroke.dasharray {
-        content.set_dash_pattern(
-            dasharray.iter().map(|&x| x as f32),
-            stroke.dashoffset,
-        );
-    }
-
+fn set_stroke_paint(
+    stroke: &Stroke,
+    node: &Node,
+    content: &mut Content,
+    writer: &mut PdfWriter,
+    ctx: &mut Context,
+) {
     match &stroke.paint {
         Paint::Color(c) => {
             content.set_stroke_color(RgbColor::from(*c).to_array());
         }
-        _ => todo!(),
+        Paint::Pattern(p) => {
+            let pattern_name = create_pattern(p.clone(), &node.parent().unwrap(), writer, ctx);
+            content.set_stroke_color_space(Pattern);
+            content.set_stroke_pattern(None, pattern_name.as_name());
+        }
+        Paint::LinearGradient(_) | Paint::RadialGradient(_) => {
+            if let Some(bbox) = node_bbox(node) {
+                let accumulated_transform = ctx.context_frame.transform();
+                if let Some((shading_name, soft_mask)) =
+                    gradient::create(&stroke.paint, &bbox, writer, ctx, &accumulated_transform)
+                {
+                    content.set_stroke_color_space(Pattern);
+                    content.set_stroke_pattern(None, shading_name.as_name());
+
+                    if let Some(soft_mask) = soft_mask {
+                        content.set_parameters(soft_mask.as_name());
+                    }
+                }
+            }
+        }
     }
 }
 
-fn set_fill(fill: &Fill, content: &mut Content) {
+fn set_fill(
+    fill: &Fill,
+    node: &Node,
+    content: &mut Content,
+    writer: &mut PdfWriter,
+    ctx: &mut Context,
+) {
     let paint = &fill.paint;
 
     match paint {
         Paint::Color(c) => {
             content.set_fill_color(RgbColor::from(*c).to_array());
         }
-        _ => {}
+        Paint::Pattern(p) => {
+            let pattern_name = create_pattern(p.clone(), &node.parent().unwrap(), writer, ctx);
+            content.set_fill_color_space(Pattern);
+            content.set_fill_pattern(None, pattern_name.as_name());
+        }
+        Paint::LinearGradient(_) | Paint::RadialGradient(_) => {
+            if let Some(bbox) = node_bbox(node) {
+                let accumulated_transform = ctx.context_frame.transform();
+                if let Some((shading_name, soft_mask)) =
+                    gradient::create(paint, &bbox, writer, ctx, &accumulated_transform)
+                {
+                    content.set_fill_color_space(Pattern);
+                    content.set_fill_pattern(None, shading_name.as_name());
+
+                    if let Some(soft_mask) = soft_mask {
+                        content.set_parameters(soft_mask.as_name());
+                    }
+                }
+            }
+        }
     }
+}
+
+/// Whether `paint` (absent, i.e. no stroke/fill at all, or present) needs nothing
+/// beyond a plain color — no PDF pattern/shading resource to allocate.
+fn is_plain_paint(paint: Option<&Paint>) -> bool {
+    !matches!(paint, Some(Paint::Pattern(_) | Paint::LinearGradient(_) | Paint::RadialGradient(_)))
+}
+
+fn node_bbox(node: &Node) -> Option<NonZeroRect> {
+    let bbox = node.calculate_bbox()?.to_rect()?;
+    NonZeroRect::from_xywh(bbox.x(), bbox.y(), bbox.width(), bbox.height())
 }
\ No newline at end of file